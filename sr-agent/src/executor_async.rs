@@ -0,0 +1,397 @@
+//! The render executor, built on `tokio::process::Command`. Stdout,
+//! stderr, the timeout deadline, and abort are all driven from a single
+//! task via `tokio::select!`, and the event channel itself (bounded)
+//! applies backpressure to that task instead of an executor-managed ring
+//! buffer.
+//!
+//! `main()` builds one `tokio::runtime::Runtime` for the process and keeps
+//! it alive for as long as the agent runs, entering it just long enough to
+//! spawn each render via `Runtime::block_on`. Nothing else about the main
+//! loop needs to be async — `poll_events`/`abort_graceful`/`kill_immediately`
+//! are all plain synchronous methods, so this executor drops into the same
+//! non-blocking poll loop a thread-based one would.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::{Duration, Instant};
+
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::{Child, ChildStderr, ChildStdout, Command};
+use tokio::sync::{mpsc, Notify};
+use tokio::task::JoinHandle;
+
+use crate::executor::{send_soft_signal, RenderEvent};
+use crate::messages::TaskMessage;
+use crate::parser::{CompletionParser, OutputParser, ProgressParser};
+use crate::watchdog::{self, Watchdog};
+
+/// Bounded so a slow monitor naturally slows down this task's stdout/stderr
+/// readers rather than letting the executor accumulate an unbounded queue.
+const EVENT_CHANNEL_CAPACITY: usize = 256;
+
+pub struct AsyncRenderExecutor {
+    /// `tokio::sync::mpsc::Receiver::try_recv` takes `&mut self`; wrapped
+    /// so `poll_events` can keep the same `&self` shape the main loop uses
+    /// for every other executor call.
+    event_rx: StdMutex<mpsc::Receiver<RenderEvent>>,
+    event_tx: mpsc::Sender<RenderEvent>,
+    abort_notify: Arc<Notify>,
+    grace_watchdog: Arc<StdMutex<Option<Watchdog>>>,
+    grace_killed: Arc<AtomicBool>,
+    worker: JoinHandle<()>,
+    pid: u32,
+    pub job_id: String,
+    pub frame_start: u32,
+    pub frame_end: u32,
+}
+
+impl AsyncRenderExecutor {
+    /// Spawn the render. Must be called from within a tokio runtime
+    /// context (e.g. via `Runtime::block_on`) so the internal
+    /// `tokio::spawn` has somewhere to run — the returned executor is
+    /// driven entirely by that spawned task afterward, not by the caller.
+    pub async fn start(task: TaskMessage) -> Result<Self, String> {
+        let job_id = task.job_id.clone();
+        let frame_start = task.frame_start;
+        let frame_end = task.frame_end;
+
+        let mut cmd = Command::new(&task.command.executable);
+        cmd.args(&task.command.args);
+        cmd.stdout(std::process::Stdio::piped());
+        cmd.stderr(std::process::Stdio::piped());
+        // Kills the direct child when the task that owns it is dropped;
+        // `Drop for AsyncRenderExecutor` aborts that task, so dropping the
+        // executor before the render finishes takes the child down with it.
+        // `process::exit` runs no destructors, so exit paths kill the
+        // process tree explicitly instead (`kill_immediately`).
+        cmd.kill_on_drop(true);
+
+        // Run the render in its own process group/job so a soft shutdown
+        // signal — and `kill_process_tree` — can target the whole tree a
+        // render wrapper forks, not just the immediate child.
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            cmd.process_group(0);
+        }
+        #[cfg(windows)]
+        {
+            use std::os::windows::process::CommandExt;
+            const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+            cmd.creation_flags(CREATE_NEW_PROCESS_GROUP);
+        }
+
+        if let Some(ref wd) = task.working_dir {
+            if !wd.is_empty() {
+                cmd.current_dir(wd);
+            }
+        }
+        for (k, v) in &task.environment {
+            cmd.env(k, v);
+        }
+
+        let mut child = cmd
+            .spawn()
+            .map_err(|e| format!("Failed to spawn process: {}", e))?;
+        let pid = child
+            .id()
+            .ok_or_else(|| "child exited before its pid could be read".to_string())?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| "Failed to capture stdout".to_string())?;
+        let stderr = child
+            .stderr
+            .take()
+            .ok_or_else(|| "Failed to capture stderr".to_string())?;
+
+        let (event_tx, event_rx) = mpsc::channel(EVENT_CHANNEL_CAPACITY);
+        let abort_notify = Arc::new(Notify::new());
+        let grace_watchdog: Arc<StdMutex<Option<Watchdog>>> = Arc::new(StdMutex::new(None));
+        let grace_killed = Arc::new(AtomicBool::new(false));
+
+        let progress_parser = task.progress.as_ref().map(ProgressParser::new);
+        let output_parser = task.output_detection.as_ref().and_then(OutputParser::new);
+        let completion_parser = task
+            .progress
+            .as_ref()
+            .and_then(|spec| spec.completion_pattern.as_ref())
+            .and_then(CompletionParser::new);
+        let timeout = task.timeout_seconds.map(Duration::from_secs);
+
+        let worker = tokio::spawn(run(
+            child,
+            stdout,
+            stderr,
+            event_tx.clone(),
+            abort_notify.clone(),
+            grace_watchdog.clone(),
+            grace_killed.clone(),
+            progress_parser,
+            output_parser,
+            completion_parser,
+            timeout,
+            pid,
+            frame_start,
+            frame_end,
+        ));
+
+        Ok(Self {
+            event_rx: StdMutex::new(event_rx),
+            event_tx,
+            abort_notify,
+            grace_watchdog,
+            grace_killed,
+            worker,
+            pid,
+            job_id,
+            frame_start,
+            frame_end,
+        })
+    }
+
+    /// Non-blocking poll for render events. Draws straight from the
+    /// bounded event channel, which is already what applies backpressure
+    /// to the worker task — there's no separate ring buffer to drain.
+    pub fn poll_events(&self) -> Vec<RenderEvent> {
+        let mut events = Vec::new();
+        if let Ok(mut rx) = self.event_rx.lock() {
+            while let Ok(ev) = rx.try_recv() {
+                events.push(ev);
+            }
+        }
+        events
+    }
+
+    /// Hard-kill this render's process tree right now, bypassing any
+    /// cooperative wait entirely. Used when the agent itself is about to
+    /// exit and must guarantee the child (and anything it forked into its
+    /// process group) doesn't outlive it as an orphan still pinning the
+    /// GPU.
+    pub fn kill_immediately(&self) {
+        watchdog::kill_process_tree(self.pid);
+        self.abort_notify.notify_one();
+    }
+
+    /// Escalating abort: send a soft shutdown signal to the whole process
+    /// group, give the renderer up to `grace` to exit on its own, and only
+    /// then fall back to a hard kill of the process tree. A well-behaved
+    /// renderer gets a chance to flush partial frames and release licenses
+    /// first.
+    pub fn abort_graceful(&self, grace: Duration) {
+        log::info!(
+            "Sending soft shutdown signal to pid={} (grace={:?})",
+            self.pid,
+            grace,
+        );
+        send_soft_signal(self.pid);
+        let _ = self.event_tx.try_send(RenderEvent::Aborting);
+
+        let pid = self.pid;
+        let tx = self.event_tx.clone();
+        let grace_killed = self.grace_killed.clone();
+        let watchdog = Watchdog::start(grace, move || {
+            grace_killed.store(true, Ordering::SeqCst);
+            log::warn!(
+                "pid={} ignored soft shutdown signal, escalating to hard kill after {:?}",
+                pid,
+                grace,
+            );
+            watchdog::kill_process_tree(pid);
+            let _ = tx.try_send(RenderEvent::Failed {
+                exit_code: -1,
+                error: "killed after grace period expired".into(),
+            });
+        });
+        if let Ok(mut guard) = self.grace_watchdog.lock() {
+            *guard = Some(watchdog);
+        }
+    }
+
+    /// Check if the render's worker task has finished.
+    pub fn is_done(&self) -> bool {
+        self.worker.is_finished()
+    }
+}
+
+impl Drop for AsyncRenderExecutor {
+    /// Abort the worker task so the `Child` it owns is dropped and
+    /// `kill_on_drop` kills it, instead of the task running on detached.
+    fn drop(&mut self) {
+        self.worker.abort();
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn run(
+    mut child: Child,
+    stdout: ChildStdout,
+    stderr: ChildStderr,
+    tx: mpsc::Sender<RenderEvent>,
+    abort_notify: Arc<Notify>,
+    grace_watchdog: Arc<StdMutex<Option<Watchdog>>>,
+    grace_killed: Arc<AtomicBool>,
+    progress_parser: Option<ProgressParser>,
+    output_parser: Option<OutputParser>,
+    completion_parser: Option<CompletionParser>,
+    timeout: Option<Duration>,
+    pid: u32,
+    frame_start: u32,
+    frame_end: u32,
+) {
+    let start = Instant::now();
+    let _ = tx.send(RenderEvent::Started).await;
+
+    let mut stdout_lines = BufReader::new(stdout).lines();
+    let mut stderr_lines = BufReader::new(stderr).lines();
+    let mut last_output_file: Option<String> = None;
+    let mut completion_count: u32 = 0;
+
+    // With no timeout requested, `sleep` still gets a far-future deadline
+    // so it need not be an `Option`; the select arm below is disabled in
+    // that case by its `timeout.is_some()` guard.
+    let sleep = tokio::time::sleep(timeout.unwrap_or(Duration::from_secs(u64::MAX / 2)));
+    tokio::pin!(sleep);
+
+    let mut stdout_done = false;
+    let mut stderr_done = false;
+    let mut timed_out = false;
+    let mut aborted = false;
+
+    loop {
+        if stdout_done && stderr_done {
+            break;
+        }
+
+        tokio::select! {
+            line = stdout_lines.next_line(), if !stdout_done => {
+                match line {
+                    Ok(Some(l)) => {
+                        if let Some(ref parser) = progress_parser {
+                            if let Some(pct) = parser.parse_line(&l) {
+                                let _ = tx.send(RenderEvent::Progress {
+                                    pct,
+                                    elapsed_ms: start.elapsed().as_millis() as u64,
+                                }).await;
+                            }
+                        }
+                        if let Some(ref parser) = output_parser {
+                            if let Some(path) = parser.parse_line(&l) {
+                                last_output_file = Some(path);
+                            }
+                        }
+                        if let Some(ref parser) = completion_parser {
+                            if parser.matches(&l) {
+                                let frame = frame_start + completion_count;
+                                completion_count += 1;
+                                if frame <= frame_end {
+                                    let _ = tx.send(RenderEvent::FrameCompleted { frame }).await;
+                                }
+                            }
+                        }
+                        let _ = tx.send(RenderEvent::Stdout(vec![l])).await;
+                    }
+                    _ => stdout_done = true,
+                }
+            }
+            line = stderr_lines.next_line(), if !stderr_done => {
+                match line {
+                    Ok(Some(l)) => {
+                        if let Some(ref parser) = completion_parser {
+                            if parser.matches(&l) {
+                                let frame = frame_start + completion_count;
+                                completion_count += 1;
+                                if frame <= frame_end {
+                                    let _ = tx.send(RenderEvent::FrameCompleted { frame }).await;
+                                }
+                            }
+                        }
+                        let _ = tx.send(RenderEvent::Stdout(vec![format!("[stderr] {}", l)])).await;
+                    }
+                    _ => stderr_done = true,
+                }
+            }
+            _ = &mut sleep, if timeout.is_some() => {
+                timed_out = true;
+                let secs = timeout.unwrap().as_secs();
+                log::warn!(
+                    "Render timed out after {}s (chunk {}-{}), killing process tree pid={}",
+                    secs, frame_start, frame_end, pid,
+                );
+                watchdog::kill_process_tree(pid);
+                let _ = tx.send(RenderEvent::Failed {
+                    exit_code: watchdog::TIMEOUT_EXIT_CODE,
+                    error: format!("timeout after {}s", secs),
+                }).await;
+                break;
+            }
+            _ = abort_notify.notified() => {
+                // `kill_immediately` already hard-killed the process tree
+                // directly; this just stops the reader loop so `wait()`
+                // below observes the kill instead of a natural exit.
+                aborted = true;
+                break;
+            }
+        }
+    }
+
+    // The grace watchdog (if any) is a plain thread independent of this
+    // task — cancel it on any of this task's own exit paths so it doesn't
+    // fire a redundant hard-kill/Failed after the render is already done.
+    if let Ok(mut g) = grace_watchdog.lock() {
+        if let Some(w) = g.take() {
+            w.cancel();
+        }
+    }
+
+    let status = child.wait().await;
+
+    // The inline timeout branch above, or the escalating grace-kill
+    // running concurrently on its own thread, may already have reported
+    // the terminal event for this chunk — a wait() observed afterward
+    // must not produce a second one.
+    if timed_out || grace_killed.load(Ordering::SeqCst) {
+        return;
+    }
+
+    if aborted {
+        let _ = tx
+            .send(RenderEvent::Failed {
+                exit_code: -1,
+                error: "Aborted by monitor".into(),
+            })
+            .await;
+        return;
+    }
+
+    match status {
+        Ok(status) => {
+            let elapsed_ms = start.elapsed().as_millis() as u64;
+            let exit_code = status.code().unwrap_or(-1);
+            if status.success() {
+                let _ = tx
+                    .send(RenderEvent::Completed {
+                        elapsed_ms,
+                        exit_code,
+                        output_file: last_output_file,
+                    })
+                    .await;
+            } else {
+                let _ = tx
+                    .send(RenderEvent::Failed {
+                        exit_code,
+                        error: format!("Process exited with code {}", exit_code),
+                    })
+                    .await;
+            }
+        }
+        Err(e) => {
+            let _ = tx
+                .send(RenderEvent::Failed {
+                    exit_code: -1,
+                    error: format!("Failed to wait for process: {}", e),
+                })
+                .await;
+        }
+    }
+}
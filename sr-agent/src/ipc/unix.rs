@@ -0,0 +1,98 @@
+use std::env;
+use std::io::{self, Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+
+/// Unix domain socket client for communicating with the monitor.
+///
+/// Mirrors `WindowsPipeClient`'s connect/peek/read/write surface so the
+/// agent loop in `main()` runs unchanged on Linux and macOS render nodes.
+pub struct UnixPipeClient {
+    stream: UnixStream,
+}
+
+impl UnixPipeClient {
+    /// Connect to the monitor's socket for the given node_id.
+    /// Retries up to 3 times with 3-second intervals.
+    pub fn connect(node_id: &str) -> io::Result<Self> {
+        let path = socket_path(node_id);
+
+        let max_attempts = 3;
+        let retry_delay = Duration::from_secs(3);
+
+        for attempt in 1..=max_attempts {
+            log::info!(
+                "Connecting to socket: {} (attempt {}/{})",
+                path.display(),
+                attempt,
+                max_attempts
+            );
+
+            match UnixStream::connect(&path) {
+                Ok(stream) => {
+                    log::info!("Connected to monitor socket");
+                    return Ok(Self { stream });
+                }
+                Err(e) if attempt < max_attempts => {
+                    log::warn!("Socket not available yet ({}), retrying in {}s...", e, retry_delay.as_secs());
+                    thread::sleep(retry_delay);
+                }
+                Err(e) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::ConnectionRefused,
+                        format!("Failed to connect to socket {} after {} attempts: {}", path.display(), max_attempts, e),
+                    ));
+                }
+            }
+        }
+
+        unreachable!()
+    }
+
+    /// Check whether bytes are available to read without blocking: 1 if at
+    /// least one byte is waiting, 0 if none is (a peer that has closed the
+    /// socket also reads as 0). Emulated with a non-blocking `MSG_PEEK` recv, since `UnixStream::peek`
+    /// otherwise blocks just like a normal read when the stream has no
+    /// pending data.
+    pub fn peek_available(&self) -> io::Result<usize> {
+        self.stream.set_nonblocking(true)?;
+        let mut buf = [0u8; 1];
+        let result = self.stream.peek(&mut buf);
+        self.stream.set_nonblocking(false)?;
+
+        match result {
+            Ok(n) => Ok(n),
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => Ok(0),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Resolve the socket path for a node_id, preferring `$XDG_RUNTIME_DIR`
+/// over `/tmp` so sockets land in a per-user, non-world-readable directory
+/// when one is available.
+fn socket_path(node_id: &str) -> PathBuf {
+    let file_name = format!("SmallRenderAgent_{}.sock", node_id);
+    match env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) => PathBuf::from(dir).join(file_name),
+        None => PathBuf::from("/tmp").join(file_name),
+    }
+}
+
+impl Read for UnixPipeClient {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.stream.read(buf)
+    }
+}
+
+impl Write for UnixPipeClient {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.stream.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream.flush()
+    }
+}
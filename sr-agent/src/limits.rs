@@ -0,0 +1,99 @@
+//! Raises this process's open-file-descriptor limit once at startup.
+//! Each concurrent render pipes stdout+stderr back to this agent and the
+//! renderer itself typically opens many texture/cache files; on macOS the
+//! default soft `RLIMIT_NOFILE` (often 256) is exhausted quickly under
+//! multi-slot rendering, surfacing as spurious "Too many open files"
+//! spawn failures in `AsyncRenderExecutor::start`.
+
+/// A sane ceiling to raise to when the hard limit itself reports
+/// unlimited (`RLIM_INFINITY`) — mirrors the role `OPEN_MAX` plays on
+/// platforms that define it, without assuming every Unix target does.
+#[cfg(unix)]
+const FALLBACK_CEILING: libc::rlim_t = 65536;
+
+#[cfg(unix)]
+pub fn raise_fd_limit() {
+    let mut lim = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    if unsafe { libc::getrlimit(libc::RLIMIT_NOFILE, &mut lim) } != 0 {
+        log::warn!(
+            "Failed to read RLIMIT_NOFILE: {}",
+            std::io::Error::last_os_error(),
+        );
+        return;
+    }
+
+    let old_soft = lim.rlim_cur;
+    let target = clamp_to_os_ceiling(if lim.rlim_max == libc::RLIM_INFINITY {
+        FALLBACK_CEILING
+    } else {
+        lim.rlim_max
+    });
+
+    if target <= old_soft {
+        log::info!(
+            "Open-file limit already at {} (hard limit {}), leaving as-is",
+            old_soft,
+            lim.rlim_max,
+        );
+        return;
+    }
+
+    lim.rlim_cur = target;
+    if unsafe { libc::setrlimit(libc::RLIMIT_NOFILE, &lim) } == 0 {
+        log::info!("Raised open-file limit from {} to {}", old_soft, target);
+    } else {
+        log::warn!(
+            "Failed to raise open-file limit from {} to {}: {}",
+            old_soft,
+            target,
+            std::io::Error::last_os_error(),
+        );
+    }
+}
+
+/// Clamp the target soft limit to whatever additional ceiling the current
+/// OS imposes beyond `RLIMIT_NOFILE` itself — only macOS has one.
+#[cfg(target_os = "macos")]
+fn clamp_to_os_ceiling(target: libc::rlim_t) -> libc::rlim_t {
+    match darwin_max_files_per_proc() {
+        Some(max_per_proc) => target.min(max_per_proc),
+        None => target,
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn clamp_to_os_ceiling(target: libc::rlim_t) -> libc::rlim_t {
+    target
+}
+
+/// `kern.maxfilesperproc` is Darwin's real per-process ceiling — the hard
+/// `RLIMIT_NOFILE` reported by `getrlimit` can claim a higher value than
+/// the kernel will actually honor.
+#[cfg(target_os = "macos")]
+fn darwin_max_files_per_proc() -> Option<libc::rlim_t> {
+    let name = std::ffi::CString::new("kern.maxfilesperproc").ok()?;
+    let mut value: libc::c_int = 0;
+    let mut size = std::mem::size_of::<libc::c_int>();
+    let ret = unsafe {
+        libc::sysctlbyname(
+            name.as_ptr(),
+            &mut value as *mut _ as *mut libc::c_void,
+            &mut size,
+            std::ptr::null_mut(),
+            0,
+        )
+    };
+    if ret == 0 && value > 0 {
+        Some(value as libc::rlim_t)
+    } else {
+        None
+    }
+}
+
+#[cfg(not(unix))]
+pub fn raise_fd_limit() {
+    log::debug!("Open-file-descriptor limit raising is a no-op on this platform");
+}
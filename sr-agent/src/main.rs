@@ -1,18 +1,45 @@
 mod executor;
+mod executor_async;
 mod ipc;
+mod limits;
 mod messages;
 mod parser;
 mod watchdog;
 
 use clap::Parser;
-use executor::{RenderEvent, RenderExecutor};
+use executor::RenderEvent;
+use executor_async::AsyncRenderExecutor;
+use hmac::{Hmac, Mac};
 use messages::{
-    AckMessage, AgentToMonitor, CompletedMessage, FailedMessage, FrameCompletedMessage,
-    MonitorToAgent, ProgressMessage, StatusMessage, StdoutMessage,
+    AckMessage, AgentToMonitor, AuthProofMessage, CompletedMessage, FailedMessage,
+    FrameCompletedMessage, HelloMessage, KeepaliveMessage, MonitorToAgent, NackMessage,
+    ProgressMessage, StatusMessage, StdoutMessage, TaskMessage,
 };
+use sha2::Sha256;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
 use std::process;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// How long a monitor-requested shutdown/abort gives a render to exit on
+/// its own after the soft signal before escalating to a hard kill.
+const ABORT_GRACE: Duration = Duration::from_secs(5);
+
+/// Bound on how long this agent waits for `kill_process_tree` to actually
+/// take effect before giving up and exiting anyway — a bound, not a grace
+/// period, since the kill itself is already unconditional by this point.
+const RENDER_KILL_CONFIRM_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Identifies one in-flight chunk: (job_id, frame_start, frame_end).
+type ChunkKey = (String, u32, u32);
+
+fn chunk_key(task: &TaskMessage) -> ChunkKey {
+    (task.job_id.clone(), task.frame_start, task.frame_end)
+}
 
 #[derive(Parser)]
 #[command(name = "sr-agent", about = "SmallRender headless render agent", version)]
@@ -20,6 +47,89 @@ struct Args {
     /// Node ID to connect to (must match the monitor's node ID)
     #[arg(long)]
     node_id: String,
+
+    /// One-time shared secret used to authenticate the monitor connection
+    #[arg(long)]
+    auth_token: String,
+
+    /// Number of chunks this agent can render concurrently
+    #[arg(long, default_value_t = 1)]
+    slots: usize,
+
+    /// Expected interval between monitor pings; also the cadence of this
+    /// agent's own proactive keepalives
+    #[arg(long, default_value_t = 30)]
+    ping_interval_secs: u64,
+
+    /// How long this agent tolerates silence from the monitor before
+    /// aborting active renders and exiting. Defaults to 3x
+    /// --ping-interval-secs.
+    #[arg(long)]
+    monitor_timeout_secs: Option<u64>,
+}
+
+/// A jobserver-style pool of render slots. Only ever touched from the main
+/// loop, so it's plain counters rather than anything synchronized.
+struct TokenPool {
+    total: usize,
+    in_use: usize,
+}
+
+impl TokenPool {
+    fn new(total: usize) -> Self {
+        Self { total: total.max(1), in_use: 0 }
+    }
+
+    fn try_acquire(&mut self) -> bool {
+        if self.in_use < self.total {
+            self.in_use += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn release(&mut self) {
+        self.in_use = self.in_use.saturating_sub(1);
+    }
+}
+
+#[cfg(test)]
+mod token_pool_tests {
+    use super::*;
+
+    #[test]
+    fn new_clamps_zero_slots_to_one() {
+        let mut pool = TokenPool::new(0);
+        assert!(pool.try_acquire());
+        assert!(!pool.try_acquire());
+    }
+
+    #[test]
+    fn acquires_up_to_total_then_saturates() {
+        let mut pool = TokenPool::new(2);
+        assert!(pool.try_acquire());
+        assert!(pool.try_acquire());
+        assert!(!pool.try_acquire());
+    }
+
+    #[test]
+    fn release_frees_a_slot_for_reacquisition() {
+        let mut pool = TokenPool::new(1);
+        assert!(pool.try_acquire());
+        assert!(!pool.try_acquire());
+        pool.release();
+        assert!(pool.try_acquire());
+    }
+
+    #[test]
+    fn release_without_a_matching_acquire_does_not_underflow() {
+        let mut pool = TokenPool::new(1);
+        pool.release();
+        pool.release();
+        assert!(pool.try_acquire());
+        assert!(!pool.try_acquire());
+    }
 }
 
 /// Holds the named mutex handle to keep it alive for the process lifetime.
@@ -71,6 +181,8 @@ fn main() {
     let args = Args::parse();
     log::info!("sr-agent starting for node_id={}", args.node_id);
 
+    limits::raise_fd_limit();
+
     // Single instance check — prevent duplicate agents for the same node
     let _mutex_guard = ensure_single_instance(&args.node_id);
 
@@ -82,6 +194,14 @@ fn main() {
         }
     };
 
+    // Handshake: prove knowledge of --auth-token and bind to the monitor's
+    // PID before accepting any task traffic.
+    let mut bound_monitor_pid: Option<u32> = None;
+    if let Err(e) = perform_handshake(&mut pipe, &args.node_id, &args.auth_token, &mut bound_monitor_pid) {
+        log::error!("Handshake with monitor failed: {}", e);
+        process::exit(1);
+    }
+
     // Send initial status: idle + our PID
     let status = AgentToMonitor::Status(StatusMessage {
         state: "idle".into(),
@@ -93,116 +213,187 @@ fn main() {
     }
     log::info!("Sent initial status (pid={})", process::id());
 
-    let mut active_render: Option<RenderExecutor> = None;
+    // Kept alive for the process lifetime; `block_on` below only needs a
+    // moment inside this runtime to spawn each render, not to be awaited
+    // from one — the rest of this loop stays a plain synchronous poll.
+    let rt = match tokio::runtime::Builder::new_multi_thread().enable_all().build() {
+        Ok(rt) => rt,
+        Err(e) => {
+            log::error!("Failed to start tokio runtime: {}", e);
+            process::exit(1);
+        }
+    };
+
+    let mut active_render: HashMap<ChunkKey, AsyncRenderExecutor> = HashMap::new();
+    let mut tokens = TokenPool::new(args.slots);
+
+    let ping_interval = Duration::from_secs(args.ping_interval_secs.max(1));
+    let monitor_timeout = Duration::from_secs(
+        args.monitor_timeout_secs.unwrap_or(args.ping_interval_secs.max(1) * 3),
+    );
+    let mut last_monitor_contact = Instant::now();
+    let mut last_keepalive_sent = Instant::now();
+    let mut keepalive_seq: u64 = 0;
 
     loop {
-        if let Some(ref executor) = active_render {
+        // Bidirectional heartbeat: bail out if the monitor has gone dark,
+        // rather than waiting forever on a pipe a crashed monitor will
+        // never write to again.
+        if last_monitor_contact.elapsed() > monitor_timeout {
+            log::error!(
+                "No contact from monitor in {:.1}s (limit {:.1}s), killing {} active chunk(s) and exiting",
+                last_monitor_contact.elapsed().as_secs_f64(),
+                monitor_timeout.as_secs_f64(),
+                active_render.len(),
+            );
+            // process::exit skips destructors, so no executor's drop-time
+            // kill will run — kill the process tree directly and wait (bounded) for
+            // confirmation before this agent disappears, or the render
+            // becomes an orphan still pinning the GPU.
+            kill_all_and_wait(&active_render, RENDER_KILL_CONFIRM_TIMEOUT);
+            process::exit(1);
+        }
+
+        if last_keepalive_sent.elapsed() >= ping_interval {
+            keepalive_seq += 1;
+            let _ = send_message(
+                &mut pipe,
+                &AgentToMonitor::Keepalive(KeepaliveMessage {
+                    seq: keepalive_seq,
+                    in_flight: active_render.len() as u32,
+                }),
+            );
+            last_keepalive_sent = Instant::now();
+        }
+
+        if !active_render.is_empty() {
             // === RENDERING MODE ===
-            let mut done = false;
-
-            // 1. Process render events (non-blocking)
-            for event in executor.poll_events() {
-                match event {
-                    RenderEvent::Started => {
-                        log::info!(
-                            "Render started: job={} chunk={}-{}",
-                            executor.job_id,
-                            executor.frame_start,
-                            executor.frame_end,
-                        );
-                        let _ = send_message(
-                            &mut pipe,
-                            &AgentToMonitor::Ack(AckMessage {
-                                job_id: executor.job_id.clone(),
-                                frame_start: executor.frame_start,
-                                frame_end: executor.frame_end,
-                            }),
-                        );
-                    }
-                    RenderEvent::Stdout(lines) => {
-                        let _ = send_message(
-                            &mut pipe,
-                            &AgentToMonitor::Stdout(StdoutMessage {
-                                job_id: executor.job_id.clone(),
-                                frame_start: executor.frame_start,
-                                frame_end: executor.frame_end,
-                                lines,
-                            }),
-                        );
-                    }
-                    RenderEvent::Progress { pct, elapsed_ms } => {
-                        let _ = send_message(
-                            &mut pipe,
-                            &AgentToMonitor::Progress(ProgressMessage {
-                                job_id: executor.job_id.clone(),
-                                frame_start: executor.frame_start,
-                                frame_end: executor.frame_end,
-                                progress_pct: pct,
-                                elapsed_ms,
-                            }),
-                        );
-                    }
-                    RenderEvent::FrameCompleted { frame } => {
-                        let _ = send_message(
-                            &mut pipe,
-                            &AgentToMonitor::FrameCompleted(FrameCompletedMessage {
-                                job_id: executor.job_id.clone(),
-                                frame,
-                            }),
-                        );
-                    }
-                    RenderEvent::Completed {
-                        elapsed_ms,
-                        exit_code,
-                        output_file,
-                    } => {
-                        log::info!(
-                            "Render completed: job={} chunk={}-{} exit_code={} elapsed={}ms",
-                            executor.job_id,
-                            executor.frame_start,
-                            executor.frame_end,
-                            exit_code,
+            let mut finished = Vec::new();
+
+            // 1. Process render events for every active chunk (non-blocking)
+            for (key, executor) in active_render.iter() {
+                for event in executor.poll_events() {
+                    match event {
+                        RenderEvent::Started => {
+                            log::info!(
+                                "Render started: job={} chunk={}-{}",
+                                executor.job_id,
+                                executor.frame_start,
+                                executor.frame_end,
+                            );
+                            let _ = send_message(
+                                &mut pipe,
+                                &AgentToMonitor::Ack(AckMessage {
+                                    job_id: executor.job_id.clone(),
+                                    frame_start: executor.frame_start,
+                                    frame_end: executor.frame_end,
+                                }),
+                            );
+                        }
+                        RenderEvent::Aborting => {
+                            log::info!(
+                                "Render aborting (soft signal sent): job={} chunk={}-{}",
+                                executor.job_id,
+                                executor.frame_start,
+                                executor.frame_end,
+                            );
+                            let _ = send_message(
+                                &mut pipe,
+                                &AgentToMonitor::Status(StatusMessage {
+                                    state: "aborting".into(),
+                                    pid: process::id(),
+                                }),
+                            );
+                        }
+                        RenderEvent::Stdout(lines) => {
+                            let _ = send_message(
+                                &mut pipe,
+                                &AgentToMonitor::Stdout(StdoutMessage {
+                                    job_id: executor.job_id.clone(),
+                                    frame_start: executor.frame_start,
+                                    frame_end: executor.frame_end,
+                                    lines,
+                                }),
+                            );
+                        }
+                        RenderEvent::Progress { pct, elapsed_ms } => {
+                            let _ = send_message(
+                                &mut pipe,
+                                &AgentToMonitor::Progress(ProgressMessage {
+                                    job_id: executor.job_id.clone(),
+                                    frame_start: executor.frame_start,
+                                    frame_end: executor.frame_end,
+                                    progress_pct: pct,
+                                    elapsed_ms,
+                                }),
+                            );
+                        }
+                        RenderEvent::FrameCompleted { frame } => {
+                            let _ = send_message(
+                                &mut pipe,
+                                &AgentToMonitor::FrameCompleted(FrameCompletedMessage {
+                                    job_id: executor.job_id.clone(),
+                                    frame,
+                                }),
+                            );
+                        }
+                        RenderEvent::Completed {
                             elapsed_ms,
-                        );
-                        let _ = send_message(
-                            &mut pipe,
-                            &AgentToMonitor::Completed(CompletedMessage {
-                                job_id: executor.job_id.clone(),
-                                frame_start: executor.frame_start,
-                                frame_end: executor.frame_end,
-                                elapsed_ms,
-                                exit_code,
-                                output_file,
-                            }),
-                        );
-                        done = true;
-                    }
-                    RenderEvent::Failed { exit_code, error } => {
-                        log::warn!(
-                            "Render failed: job={} chunk={}-{} exit_code={} error={}",
-                            executor.job_id,
-                            executor.frame_start,
-                            executor.frame_end,
                             exit_code,
-                            error,
-                        );
-                        let _ = send_message(
-                            &mut pipe,
-                            &AgentToMonitor::Failed(FailedMessage {
-                                job_id: executor.job_id.clone(),
-                                frame_start: executor.frame_start,
-                                frame_end: executor.frame_end,
+                            output_file,
+                        } => {
+                            log::info!(
+                                "Render completed: job={} chunk={}-{} exit_code={} elapsed={}ms",
+                                executor.job_id,
+                                executor.frame_start,
+                                executor.frame_end,
+                                exit_code,
+                                elapsed_ms,
+                            );
+                            let _ = send_message(
+                                &mut pipe,
+                                &AgentToMonitor::Completed(CompletedMessage {
+                                    job_id: executor.job_id.clone(),
+                                    frame_start: executor.frame_start,
+                                    frame_end: executor.frame_end,
+                                    elapsed_ms,
+                                    exit_code,
+                                    output_file,
+                                }),
+                            );
+                            finished.push(key.clone());
+                        }
+                        RenderEvent::Failed { exit_code, error } => {
+                            log::warn!(
+                                "Render failed: job={} chunk={}-{} exit_code={} error={}",
+                                executor.job_id,
+                                executor.frame_start,
+                                executor.frame_end,
                                 exit_code,
                                 error,
-                            }),
-                        );
-                        done = true;
+                            );
+                            let _ = send_message(
+                                &mut pipe,
+                                &AgentToMonitor::Failed(FailedMessage {
+                                    job_id: executor.job_id.clone(),
+                                    frame_start: executor.frame_start,
+                                    frame_end: executor.frame_end,
+                                    exit_code,
+                                    error,
+                                }),
+                            );
+                            finished.push(key.clone());
+                        }
                     }
                 }
             }
 
-            if done {
-                active_render = None;
+            for key in finished {
+                active_render.remove(&key);
+                tokens.release();
+            }
+
+            if active_render.is_empty() {
                 let _ = send_message(
                     &mut pipe,
                     &AgentToMonitor::Status(StatusMessage {
@@ -218,110 +409,204 @@ fn main() {
             if has_data {
                 match ipc::read_message(&mut pipe) {
                     Ok(payload) => {
+                        last_monitor_contact = Instant::now();
                         if let Ok(msg) = serde_json::from_slice::<MonitorToAgent>(&payload) {
                             match msg {
                                 MonitorToAgent::Ping => {
                                     let _ = send_message(&mut pipe, &AgentToMonitor::Pong);
                                 }
                                 MonitorToAgent::Shutdown => {
-                                    log::info!("Received shutdown during render, aborting");
-                                    if let Some(ref exec) = active_render {
-                                        exec.abort();
+                                    log::info!("Received shutdown during render, gracefully aborting all chunks");
+                                    for exec in active_render.values() {
+                                        exec.abort_graceful(ABORT_GRACE);
+                                    }
+                                    // Give the escalating abort its full grace
+                                    // period (plus a little slack for the
+                                    // hard-kill fallback to land) before this
+                                    // process exits out from under it.
+                                    let deadline = Instant::now()
+                                        + ABORT_GRACE
+                                        + Duration::from_millis(500);
+                                    while Instant::now() < deadline
+                                        && active_render.values().any(|exec| !exec.is_done())
+                                    {
+                                        thread::sleep(Duration::from_millis(100));
                                     }
-                                    // Wait briefly for worker to finish
-                                    thread::sleep(Duration::from_millis(500));
                                     break;
                                 }
                                 MonitorToAgent::Abort(abort) => {
                                     log::info!("Received abort: {}", abort.reason);
-                                    if let Some(ref exec) = active_render {
-                                        exec.abort();
+                                    for exec in active_render.values() {
+                                        exec.abort_graceful(ABORT_GRACE);
                                     }
                                 }
-                                MonitorToAgent::Task(_) => {
-                                    log::warn!("Received task while already rendering, ignoring");
+                                MonitorToAgent::Task(task) => {
+                                    start_or_nack(&mut pipe, &mut active_render, &mut tokens, task, &rt);
+                                }
+                                MonitorToAgent::AuthChallenge(_) => {
+                                    log::warn!("Received unexpected re-handshake during render, ignoring");
                                 }
                             }
                         }
                     }
                     Err(e) => {
-                        log::error!("Pipe read error during render: {}", e);
-                        if let Some(ref exec) = active_render {
-                            exec.abort();
-                        }
-                        break;
+                        // A broken pipe/socket is often transient (monitor
+                        // restarted, network blip) — reconnect and keep the
+                        // renders that are still running under us, rather
+                        // than tearing down the whole agent session.
+                        log::warn!("Pipe read error during render: {}, reconnecting", e);
+                        pipe = reconnect_with_backoff(
+                            &args.node_id,
+                            &args.auth_token,
+                            &mut bound_monitor_pid,
+                            &active_render,
+                            monitor_timeout,
+                        );
+                        last_monitor_contact = Instant::now();
+                        last_keepalive_sent = Instant::now();
                     }
                 }
             }
 
             thread::sleep(Duration::from_millis(100));
         } else {
-            // === IDLE MODE === (blocking read)
-            let payload = match ipc::read_message(&mut pipe) {
-                Ok(data) => data,
-                Err(e) => {
-                    log::error!("Pipe read error (monitor disconnected?): {}", e);
-                    break;
-                }
-            };
+            // === IDLE MODE === (non-blocking poll, so heartbeat staleness
+            // is still checked even when the monitor sends nothing)
+            let has_data = pipe.peek_available().unwrap_or(0) > 0;
+            if has_data {
+                let payload = match ipc::read_message(&mut pipe) {
+                    Ok(data) => data,
+                    Err(e) => {
+                        log::warn!("Pipe read error (monitor disconnected?): {}, reconnecting", e);
+                        pipe = reconnect_with_backoff(
+                            &args.node_id,
+                            &args.auth_token,
+                            &mut bound_monitor_pid,
+                            &active_render,
+                            monitor_timeout,
+                        );
+                        last_monitor_contact = Instant::now();
+                        last_keepalive_sent = Instant::now();
+                        continue;
+                    }
+                };
+                last_monitor_contact = Instant::now();
 
-            let msg: MonitorToAgent = match serde_json::from_slice(&payload) {
-                Ok(m) => m,
-                Err(e) => {
-                    log::warn!("Failed to parse message: {}", e);
-                    continue;
-                }
-            };
+                let msg: MonitorToAgent = match serde_json::from_slice(&payload) {
+                    Ok(m) => m,
+                    Err(e) => {
+                        log::warn!("Failed to parse message: {}", e);
+                        continue;
+                    }
+                };
 
-            match msg {
-                MonitorToAgent::Ping => {
-                    log::debug!("Received ping, sending pong");
-                    if let Err(e) = send_message(&mut pipe, &AgentToMonitor::Pong) {
-                        log::error!("Failed to send pong: {}", e);
+                match msg {
+                    MonitorToAgent::Ping => {
+                        log::debug!("Received ping, sending pong");
+                        if let Err(e) = send_message(&mut pipe, &AgentToMonitor::Pong) {
+                            log::error!("Failed to send pong: {}", e);
+                            break;
+                        }
+                    }
+                    MonitorToAgent::Shutdown => {
+                        log::info!("Received shutdown command, exiting");
                         break;
                     }
-                }
-                MonitorToAgent::Shutdown => {
-                    log::info!("Received shutdown command, exiting");
-                    break;
-                }
-                MonitorToAgent::Task(task) => {
-                    log::info!(
-                        "Received task: job={} chunk={}-{} cmd={}",
-                        task.job_id,
-                        task.frame_start,
-                        task.frame_end,
-                        task.command.executable,
-                    );
-                    match RenderExecutor::start(task) {
-                        Ok(executor) => {
-                            let _ = send_message(
-                                &mut pipe,
-                                &AgentToMonitor::Status(StatusMessage {
-                                    state: "rendering".into(),
-                                    pid: process::id(),
-                                }),
-                            );
-                            active_render = Some(executor);
-                        }
-                        Err(e) => {
-                            log::error!("Failed to start render: {}", e);
-                            // Send failed message — use the info from the task
-                            // We can't access task here since it was moved, but the error
-                            // message will be logged. The monitor will detect no ack.
-                        }
+                    MonitorToAgent::Task(task) => {
+                        start_or_nack(&mut pipe, &mut active_render, &mut tokens, task, &rt);
+                    }
+                    MonitorToAgent::Abort(_) => {
+                        // Nothing to abort
+                    }
+                    MonitorToAgent::AuthChallenge(_) => {
+                        log::warn!("Received unexpected re-handshake after initial connection, ignoring");
                     }
-                }
-                MonitorToAgent::Abort(_) => {
-                    // Nothing to abort
                 }
             }
+
+            thread::sleep(Duration::from_millis(100));
         }
     }
 
     log::info!("sr-agent exiting");
 }
 
+/// Hard-kill every active render's process tree and wait (bounded) for
+/// their worker tasks to actually finish, instead of firing the kill
+/// and trusting it landed. Used on every path where this agent is about
+/// to exit out from under renders it's still tracking.
+fn kill_all_and_wait(active_render: &HashMap<ChunkKey, AsyncRenderExecutor>, timeout: Duration) {
+    for exec in active_render.values() {
+        exec.kill_immediately();
+    }
+    let deadline = Instant::now() + timeout;
+    while Instant::now() < deadline && active_render.values().any(|exec| !exec.is_done()) {
+        thread::sleep(Duration::from_millis(100));
+    }
+}
+
+/// Acquire a render slot and start the chunk, or NACK it back to the
+/// monitor so it can be re-queued elsewhere if every slot is occupied.
+fn start_or_nack(
+    pipe: &mut ipc::PipeClient,
+    active_render: &mut HashMap<ChunkKey, AsyncRenderExecutor>,
+    tokens: &mut TokenPool,
+    task: TaskMessage,
+    rt: &tokio::runtime::Runtime,
+) {
+    let key = chunk_key(&task);
+    log::info!(
+        "Received task: job={} chunk={}-{} cmd={}",
+        task.job_id,
+        task.frame_start,
+        task.frame_end,
+        task.command.executable,
+    );
+
+    if !tokens.try_acquire() {
+        log::warn!(
+            "No free render slots ({} in use), nacking job={} chunk={}-{}",
+            active_render.len(),
+            task.job_id,
+            task.frame_start,
+            task.frame_end,
+        );
+        let _ = send_message(
+            pipe,
+            &AgentToMonitor::Nack(NackMessage {
+                job_id: task.job_id,
+                frame_start: task.frame_start,
+                frame_end: task.frame_end,
+                reason: "no free render slots".into(),
+            }),
+        );
+        return;
+    }
+
+    let job_id = task.job_id.clone();
+    let frame_start = task.frame_start;
+    let frame_end = task.frame_end;
+    match rt.block_on(AsyncRenderExecutor::start(task)) {
+        Ok(executor) => {
+            let _ = send_message(
+                pipe,
+                &AgentToMonitor::Status(StatusMessage {
+                    state: "rendering".into(),
+                    pid: process::id(),
+                }),
+            );
+            active_render.insert(key, executor);
+        }
+        Err(e) => {
+            tokens.release();
+            log::error!(
+                "Failed to start render for job={} chunk={}-{}: {}",
+                job_id, frame_start, frame_end, e,
+            );
+        }
+    }
+}
+
 fn send_message(
     pipe: &mut ipc::PipeClient,
     msg: &AgentToMonitor,
@@ -330,3 +615,256 @@ fn send_message(
     ipc::write_message(pipe, &payload)?;
     Ok(())
 }
+
+/// Re-dial the monitor and redo the handshake after a transient pipe/socket
+/// break, retrying with exponential backoff instead of tearing down the
+/// agent session (and the renders still running under it) on the first
+/// blip. This loop runs instead of — not inside — the outer `main()` loop,
+/// so it enforces its own `monitor_timeout` bound directly: if the monitor
+/// is genuinely gone rather than just blipping, this kills every active
+/// render and exits rather than retrying forever with orphaned renders
+/// still tracked in `active_render`.
+fn reconnect_with_backoff(
+    node_id: &str,
+    auth_token: &str,
+    bound_monitor_pid: &mut Option<u32>,
+    active_render: &HashMap<ChunkKey, AsyncRenderExecutor>,
+    monitor_timeout: Duration,
+) -> ipc::PipeClient {
+    let mut backoff = Duration::from_secs(1);
+    let max_backoff = Duration::from_secs(30);
+    let give_up_at = Instant::now() + monitor_timeout;
+
+    loop {
+        match ipc::PipeClient::connect(node_id) {
+            Ok(mut pipe) => {
+                match perform_handshake(&mut pipe, node_id, auth_token, bound_monitor_pid) {
+                    Ok(()) => {
+                        log::info!("Reconnected to monitor after transient disconnect");
+                        return pipe;
+                    }
+                    Err(e) => {
+                        log::warn!(
+                            "Handshake failed while reconnecting: {}, retrying in {:?}",
+                            e, backoff,
+                        );
+                    }
+                }
+            }
+            Err(e) => {
+                log::warn!("Reconnect attempt failed: {}, retrying in {:?}", e, backoff);
+            }
+        }
+
+        if Instant::now() >= give_up_at {
+            log::error!(
+                "Could not reconnect to monitor within {:.1}s, killing {} active chunk(s) and exiting",
+                monitor_timeout.as_secs_f64(),
+                active_render.len(),
+            );
+            kill_all_and_wait(active_render, RENDER_KILL_CONFIRM_TIMEOUT);
+            process::exit(1);
+        }
+
+        thread::sleep(backoff.min(give_up_at.saturating_duration_since(Instant::now())));
+        backoff = (backoff * 2).min(max_backoff);
+    }
+}
+
+/// Say hello, answer the monitor's auth challenge, and bind this connection
+/// to the monitor's PID. `bound_monitor_pid` carries the PID observed on a
+/// prior successful handshake (if any) across reconnects, so a crashed and
+/// respawned rogue monitor with a different PID is rejected outright rather
+/// than silently taking over an in-flight render.
+fn perform_handshake(
+    pipe: &mut ipc::PipeClient,
+    node_id: &str,
+    auth_token: &str,
+    bound_monitor_pid: &mut Option<u32>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let nonce = generate_nonce();
+    send_message(
+        pipe,
+        &AgentToMonitor::Hello(HelloMessage {
+            node_id: node_id.to_string(),
+            pid: process::id(),
+            nonce,
+        }),
+    )?;
+
+    let payload = ipc::read_message(pipe)?;
+    let challenge = match serde_json::from_slice::<MonitorToAgent>(&payload)? {
+        MonitorToAgent::AuthChallenge(c) => c,
+        other => return Err(format!("expected AuthChallenge, got {:?}", other).into()),
+    };
+
+    check_bound_pid(*bound_monitor_pid, challenge.monitor_pid)?;
+
+    // Verify the monitor also knows the shared secret before trusting this
+    // connection with anything — otherwise whatever answers the pipe/socket
+    // first could send a bare AuthChallenge, collect our AuthProof below,
+    // and start feeding us malicious Task messages indistinguishably from
+    // the real monitor. This check is what makes that rejection happen on
+    // the very first connection, not just on a PID-mismatched reconnect.
+    // `verify_slice` rather than a hex-string `!=` is what makes this a
+    // constant-time comparison — an early-exit byte compare here would
+    // leak timing information about a secret-derived value.
+    let monitor_hmac_bytes = match decode_hex(&challenge.monitor_hmac) {
+        Some(b) => b,
+        None => return Err("monitor sent a malformed auth hmac, rejecting connection".into()),
+    };
+    if challenge_mac(auth_token, nonce)
+        .verify_slice(&monitor_hmac_bytes)
+        .is_err()
+    {
+        return Err("monitor failed to prove knowledge of the shared auth token, rejecting connection".into());
+    }
+
+    send_message(
+        pipe,
+        &AgentToMonitor::AuthProof(AuthProofMessage {
+            hmac: compute_hmac(auth_token, nonce),
+        }),
+    )?;
+
+    *bound_monitor_pid = Some(challenge.monitor_pid);
+    log::info!("Handshake complete with monitor pid={}", challenge.monitor_pid);
+    Ok(())
+}
+
+/// Reject a handshake from a different monitor PID than the one a prior
+/// handshake bound this connection to, so a crashed and respawned rogue
+/// monitor can't silently take over an in-flight render. `bound` is `None`
+/// on the very first handshake, which always succeeds.
+fn check_bound_pid(bound: Option<u32>, observed: u32) -> Result<(), String> {
+    match bound {
+        Some(expected) if expected != observed => Err(format!(
+            "monitor pid changed from {} to {}, refusing to hand off an in-flight render",
+            expected, observed
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Generate a one-time nonce for the handshake. Uniqueness, not
+/// cryptographic unpredictability, is what matters here — the actual proof
+/// of identity comes from the HMAC over this value using the shared
+/// `--auth-token`.
+fn generate_nonce() -> u64 {
+    let mut hasher = DefaultHasher::new();
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos()
+        .hash(&mut hasher);
+    process::id().hash(&mut hasher);
+    hasher.finish()
+}
+
+/// HMAC-SHA256 over the handshake nonce, keyed by the shared auth token,
+/// hex-encoded.
+fn compute_hmac(auth_token: &str, nonce: u64) -> String {
+    let mut mac = HmacSha256::new_from_slice(auth_token.as_bytes())
+        .expect("HMAC accepts a key of any length");
+    mac.update(&nonce.to_le_bytes());
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// The MAC the monitor side of the handshake must produce over the same
+/// nonce to prove it knows the shared `--auth-token`. Domain-separated
+/// from `compute_hmac`'s agent-side proof by a fixed suffix on the MAC'd
+/// bytes, so one direction's proof is never literally the same bytes as
+/// the other's. Returned unfinalized so the caller can verify it against
+/// the monitor's tag via `Mac::verify_slice` instead of finalizing to a
+/// hex string and comparing that with `==`/`!=`, which would make the
+/// comparison an early-exit byte compare rather than constant-time.
+fn challenge_mac(auth_token: &str, nonce: u64) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(auth_token.as_bytes())
+        .expect("HMAC accepts a key of any length");
+    mac.update(&nonce.to_le_bytes());
+    mac.update(b"monitor-challenge");
+    mac
+}
+
+/// Decode a lowercase hex string into bytes, as produced by `compute_hmac`
+/// and expected from the monitor's `monitor_hmac` field.
+fn decode_hex(s: &str) -> Option<Vec<u8>> {
+    if s.len() % 2 != 0 {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod handshake_tests {
+    use super::*;
+
+    #[test]
+    fn decode_hex_roundtrips_compute_hmac() {
+        let hmac = compute_hmac("shared-secret", 42);
+        let bytes = decode_hex(&hmac).unwrap();
+        let reencoded: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+        assert_eq!(reencoded, hmac);
+    }
+
+    #[test]
+    fn decode_hex_rejects_odd_length() {
+        assert_eq!(decode_hex("abc"), None);
+    }
+
+    #[test]
+    fn decode_hex_rejects_non_hex() {
+        assert_eq!(decode_hex("zz"), None);
+    }
+
+    #[test]
+    fn challenge_mac_verifies_its_own_tag() {
+        let tag = challenge_mac("shared-secret", 7).finalize().into_bytes();
+        assert!(challenge_mac("shared-secret", 7).verify_slice(&tag).is_ok());
+    }
+
+    #[test]
+    fn challenge_mac_rejects_wrong_token() {
+        let tag = challenge_mac("shared-secret", 7).finalize().into_bytes();
+        assert!(challenge_mac("other-secret", 7).verify_slice(&tag).is_err());
+    }
+
+    #[test]
+    fn challenge_mac_rejects_wrong_nonce() {
+        let tag = challenge_mac("shared-secret", 7).finalize().into_bytes();
+        assert!(challenge_mac("shared-secret", 8).verify_slice(&tag).is_err());
+    }
+
+    #[test]
+    fn challenge_mac_is_domain_separated_from_compute_hmac() {
+        // The agent's own proof and the monitor's challenge proof must
+        // never be the same bytes for the same (token, nonce) pair, or an
+        // attacker could replay one as the other.
+        let agent_proof = decode_hex(&compute_hmac("shared-secret", 7)).unwrap();
+        assert!(challenge_mac("shared-secret", 7)
+            .verify_slice(&agent_proof)
+            .is_err());
+    }
+
+    #[test]
+    fn check_bound_pid_accepts_first_handshake() {
+        assert!(check_bound_pid(None, 1234).is_ok());
+    }
+
+    #[test]
+    fn check_bound_pid_accepts_matching_pid() {
+        assert!(check_bound_pid(Some(1234), 1234).is_ok());
+    }
+
+    #[test]
+    fn check_bound_pid_rejects_mismatched_pid() {
+        assert!(check_bound_pid(Some(1234), 5678).is_err());
+    }
+}
@@ -10,6 +10,22 @@ pub enum MonitorToAgent {
     Shutdown,
     Task(TaskMessage),
     Abort(AbortMessage),
+    AuthChallenge(AuthChallengeMessage),
+}
+
+/// Sent by the monitor in reply to `AgentToMonitor::Hello`, carrying the
+/// monitor's own PID so the agent can bind the connection to it and refuse
+/// a later handshake from a different monitor PID while a render is
+/// in-flight, plus `monitor_hmac` — proof the monitor knows the shared
+/// `--auth-token` too. Without this, anything that answers first on the
+/// pipe/socket ahead of the real monitor could issue an `AuthChallenge`,
+/// collect the agent's genuine `AuthProof`, and then send `Task` messages
+/// indistinguishably from the real monitor; the agent must verify this
+/// field before trusting the connection at all, not just on reconnect.
+#[derive(Debug, Deserialize)]
+pub struct AuthChallengeMessage {
+    pub monitor_pid: u32,
+    pub monitor_hmac: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -25,6 +41,32 @@ pub struct TaskMessage {
     pub progress: Option<ProgressSpec>,
     pub output_detection: Option<OutputConfig>,
     pub timeout_seconds: Option<u64>,
+    #[serde(default)]
+    pub stdout_buffer: Option<StdoutBufferSpec>,
+}
+
+/// Stdout relay settings: a ring-buffer budget (`max_lines`, `max_bytes`)
+/// and the thresholds of a buffering→streaming phase switch. Deserialized
+/// with the task, but `AsyncRenderExecutor` does not read any of these
+/// fields: it forwards every stdout/stderr line as its own event over a
+/// bounded channel of fixed capacity, whatever this spec holds.
+#[derive(Debug, Deserialize)]
+pub struct StdoutBufferSpec {
+    #[serde(default)]
+    pub max_lines: Option<usize>,
+    #[serde(default)]
+    pub max_bytes: Option<usize>,
+    /// Lines emitted before the relay switches from batching to streaming
+    /// each line as it arrives.
+    #[serde(default)]
+    pub max_buffer_lines: Option<u64>,
+    /// Milliseconds since the render started before the same switch
+    /// happens, regardless of line count.
+    #[serde(default)]
+    pub max_buffer_time_ms: Option<u64>,
+    /// Capacity of the bounded channel backing streaming mode.
+    #[serde(default)]
+    pub stream_channel_capacity: Option<usize>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -100,6 +142,46 @@ pub enum AgentToMonitor {
     Completed(CompletedMessage),
     Failed(FailedMessage),
     FrameCompleted(FrameCompletedMessage),
+    Hello(HelloMessage),
+    AuthProof(AuthProofMessage),
+    Nack(NackMessage),
+    Keepalive(KeepaliveMessage),
+}
+
+/// Proactive liveness signal sent independently of `Pong`, so the monitor
+/// can tell a wedged-but-still-connected agent apart from one that's just
+/// between pings. `seq` increases monotonically per connection.
+#[derive(Debug, Serialize)]
+pub struct KeepaliveMessage {
+    pub seq: u64,
+    pub in_flight: u32,
+}
+
+/// Sent instead of `Ack` when a `Task` arrives but every render slot is
+/// already occupied, so the monitor can re-queue the chunk elsewhere.
+#[derive(Debug, Serialize)]
+pub struct NackMessage {
+    pub job_id: String,
+    pub frame_start: u32,
+    pub frame_end: u32,
+    pub reason: String,
+}
+
+/// First message sent on a fresh connection, before any task traffic is
+/// accepted. `nonce` is a one-time value the monitor's `AuthChallenge`
+/// implicitly asks us to prove knowledge of `--auth-token` over.
+#[derive(Debug, Serialize)]
+pub struct HelloMessage {
+    pub node_id: String,
+    pub pid: u32,
+    pub nonce: u64,
+}
+
+/// Proves knowledge of the shared `--auth-token` without sending it: an
+/// HMAC-SHA256 over the nonce from `HelloMessage`, hex-encoded.
+#[derive(Debug, Serialize)]
+pub struct AuthProofMessage {
+    pub hmac: String,
 }
 
 #[derive(Debug, Serialize)]
@@ -0,0 +1,132 @@
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Reserved exit code used to mark a render that was killed for exceeding
+/// its `timeout_seconds` deadline, distinguishing it from a renderer's own
+/// exit code.
+pub const TIMEOUT_EXIT_CODE: i32 = -62;
+
+/// Fires a callback once, unless cancelled first. Modeled on the
+/// helper-thread pattern std itself uses to emulate a timed wait on a
+/// child process: a thread parked on a condvar, woken early by whichever
+/// happens first — cancellation or the deadline.
+pub struct Watchdog {
+    state: Arc<(Mutex<bool>, Condvar)>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Watchdog {
+    pub fn start<F>(deadline: Duration, on_timeout: F) -> Self
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let state = Arc::new((Mutex::new(false), Condvar::new()));
+        let state_clone = state.clone();
+
+        let handle = thread::spawn(move || {
+            let (lock, cvar) = &*state_clone;
+            let cancelled = lock.lock().unwrap();
+            let (cancelled, wait_result) = cvar
+                .wait_timeout_while(cancelled, deadline, |cancelled| !*cancelled)
+                .unwrap();
+            if !*cancelled && wait_result.timed_out() {
+                on_timeout();
+            }
+        });
+
+        Self {
+            state,
+            handle: Some(handle),
+        }
+    }
+
+    /// Cancel the watchdog so its callback never runs. Idempotent, and
+    /// safe to call even after the deadline has already passed — the
+    /// callback only runs if cancellation didn't win the race.
+    pub fn cancel(&self) {
+        let (lock, cvar) = &*self.state;
+        let mut cancelled = lock.lock().unwrap();
+        *cancelled = true;
+        cvar.notify_all();
+    }
+}
+
+impl Drop for Watchdog {
+    fn drop(&mut self) {
+        self.cancel();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Forcibly kill a process and its descendants. Render wrappers
+/// (submission scripts, DCC launchers) commonly fork the real rendering
+/// engine as a child of their own, so killing only the immediate process
+/// leaves the expensive part of the render running. When `pid` leads a
+/// process group, every member of that group is killed as well.
+#[cfg(unix)]
+pub fn kill_process_tree(pid: u32) {
+    for descendant in descendants_of(pid) {
+        unsafe {
+            libc::kill(descendant as i32, libc::SIGKILL);
+        }
+    }
+    unsafe {
+        // The executor spawns each render as its own group leader, so this
+        // also reaches members `/proc` can't list (macOS has no `/proc`).
+        libc::kill(-(pid as i32), libc::SIGKILL);
+        libc::kill(pid as i32, libc::SIGKILL);
+    }
+}
+
+#[cfg(windows)]
+pub fn kill_process_tree(pid: u32) {
+    // `/T` recursively terminates the tree rooted at `pid`. This is only
+    // the hard fallback; the soft CTRL_BREAK half of an escalating
+    // shutdown is `executor::send_soft_signal`.
+    let _ = std::process::Command::new("taskkill")
+        .args(["/PID", &pid.to_string(), "/T", "/F"])
+        .output();
+}
+
+#[cfg(unix)]
+fn descendants_of(root: u32) -> Vec<u32> {
+    let mut children_of: std::collections::HashMap<u32, Vec<u32>> =
+        std::collections::HashMap::new();
+
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    for entry in entries.flatten() {
+        let Ok(pid) = entry.file_name().to_string_lossy().parse::<u32>() else {
+            continue;
+        };
+        if let Some(ppid) = read_ppid(pid) {
+            children_of.entry(ppid).or_default().push(pid);
+        }
+    }
+
+    let mut descendants = Vec::new();
+    let mut frontier = vec![root];
+    while let Some(parent) = frontier.pop() {
+        if let Some(children) = children_of.get(&parent) {
+            for &child in children {
+                descendants.push(child);
+                frontier.push(child);
+            }
+        }
+    }
+    descendants
+}
+
+/// Parse the parent PID out of `/proc/<pid>/stat`. The comm field can
+/// itself contain spaces/parens, so split on the *last* `)` rather than
+/// tokenizing naively.
+#[cfg(unix)]
+fn read_ppid(pid: u32) -> Option<u32> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    let after_comm = stat.rsplit_once(')')?.1;
+    after_comm.split_whitespace().nth(1)?.parse().ok()
+}